@@ -0,0 +1,27 @@
+//! Metadata about a single managed client window
+use crate::data_types::WinId;
+
+/// State tracked by the WindowManager for a single managed client window
+#[derive(Debug, Clone)]
+pub struct Client {
+    id: WinId,
+    name: String,
+    workspace: usize,
+}
+
+impl Client {
+    /// The X11 id of the underlying window for this client
+    pub fn id(&self) -> WinId {
+        self.id
+    }
+
+    /// The client's current window title
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The index of the workspace this client currently belongs to
+    pub fn workspace(&self) -> usize {
+        self.workspace
+    }
+}
@@ -0,0 +1,4 @@
+//! Common data types shared across penrose
+
+/// An X11 window ID
+pub type WinId = u32;
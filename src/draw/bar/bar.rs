@@ -6,8 +6,21 @@ use crate::{
     hooks::Hook,
     Result, WindowManager,
 };
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    time::Instant,
+};
+
+// Tolerance used when deciding whether a widget's measured width has actually changed
+// since the last layout pass. `f64::EPSILON` is too tight for this: text measurement
+// isn't guaranteed bit-for-bit reproducible between calls, and spuriously taking the
+// full-redraw path on every tick of an unchanged widget defeats the point of dirty
+// repainting.
+const WIDTH_EPSILON: f64 = 1e-6;
 
 /// The position of a status bar
+#[derive(Clone, Copy)]
 pub enum Position {
     /// Top of the screen
     Top,
@@ -15,6 +28,44 @@ pub enum Position {
     Bottom,
 }
 
+/// A message published by a widget for the [WindowManager] to act on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BarMessage {
+    /// Ask the WindowManager to switch to the workspace at the given index.
+    SwitchWorkspace(usize),
+}
+
+/// A handle passed to widgets on every hook invocation in place of polling
+/// `require_draw` after the fact. A widget calls [BarShell::request_redraw] when it
+/// changed visually and [BarShell::publish] to ask the WindowManager to do something.
+///
+/// Widgets are only ever dispatched to from an existing [Hook]/[Widget::on_tick]
+/// call: there is no X11 input event routed to a widget yet, so `publish` cannot (for
+/// example) be driven by a click on the bar itself.
+pub struct BarShell<'a> {
+    redraw_requested: bool,
+    messages: &'a mut Vec<BarMessage>,
+}
+
+impl<'a> BarShell<'a> {
+    fn new(messages: &'a mut Vec<BarMessage>) -> Self {
+        Self {
+            redraw_requested: false,
+            messages,
+        }
+    }
+
+    /// Flag that the widget holding this shell needs to be repainted.
+    pub fn request_redraw(&mut self) {
+        self.redraw_requested = true;
+    }
+
+    /// Ask the WindowManager to act on `msg`.
+    pub fn publish(&mut self, msg: BarMessage) {
+        self.messages.push(msg);
+    }
+}
+
 /// A simple status bar that works via hooks
 pub struct StatusBar<Ctx> {
     drw: Box<dyn Draw<Ctx = Ctx>>,
@@ -24,10 +75,24 @@ pub struct StatusBar<Ctx> {
     id: WinId,
     w: f64,
     h: f64,
+    // Height of the screen this bar was last built against, used to detect when a
+    // monitor's geometry has changed under us (e.g. after an `xrandr` reconfiguration).
+    sh: usize,
     bg: Color,
+    // (x_offset, width) for each widget as of the last full `layout` pass, used to
+    // work out how much of the bar actually needs to be repainted on each hook.
+    extents: Vec<(f64, f64)>,
+    // Pending `on_tick` deadlines, soonest first. Widgets with no `refresh_interval`
+    // never get an entry here.
+    timers: BinaryHeap<Reverse<(Instant, usize)>>,
 }
 impl<Ctx: DrawContext> StatusBar<Ctx> {
-    /// Try to initialise a new empty status bar. Can fail if we are unable to create our window
+    /// Try to initialise a new empty status bar. Can fail if we are unable to create our window.
+    ///
+    /// If `bg` has an alpha component below `1.0` the bar's window is created on a 32-bit
+    /// TrueColor ARGB visual so that it blends with whatever is behind it. This requires a
+    /// running compositor: without one the alpha channel is simply ignored by the X server.
+    #[allow(clippy::too_many_arguments)]
     pub fn try_new(
         mut drw: Box<dyn Draw<Ctx = Ctx>>,
         position: Position,
@@ -38,12 +103,21 @@ impl<Ctx: DrawContext> StatusBar<Ctx> {
         fonts: &[&str],
         widgets: Vec<Box<dyn Widget>>,
     ) -> Result<Self> {
+        let bg = bg.into();
         let (sw, sh) = drw.screen_size(screen_index)?;
         let y = match position {
             Position::Top => 0,
             Position::Bottom => sh - h,
         };
-        let id = drw.new_window(&WindowType::Dock, 0, y, sw, h)?;
+        let transparent = bg.alpha() < 1.0;
+        let id = drw.new_window(&WindowType::Dock, 0, y, sw, h, transparent)?;
+        let now = Instant::now();
+        let timers = widgets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, w)| w.refresh_interval().map(|d| Reverse((now + d, i))))
+            .collect();
+
         let mut bar = Self {
             drw,
             spacing,
@@ -52,7 +126,10 @@ impl<Ctx: DrawContext> StatusBar<Ctx> {
             id,
             w: sw as f64,
             h: h as f64,
-            bg: bg.into(),
+            sh,
+            bg,
+            extents: vec![],
+            timers,
         };
 
         fonts.iter().for_each(|f| bar.drw.register_font(f));
@@ -61,15 +138,57 @@ impl<Ctx: DrawContext> StatusBar<Ctx> {
         Ok(bar)
     }
 
+    /// The next point in time at which one of this bar's widgets needs to be ticked, if any.
+    /// The window manager's event loop should use this as its select/poll timeout.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.peek().map(|Reverse((deadline, _))| *deadline)
+    }
+
+    /// Tick every widget whose deadline has passed, rescheduling each against its
+    /// (possibly updated) `refresh_interval`, redraw anything that changed and return
+    /// any messages published by ticked widgets for the WindowManager to handle.
+    pub fn poll(&mut self) -> Vec<BarMessage> {
+        let now = Instant::now();
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+
+        while let Some(&Reverse((deadline, ix))) = self.timers.peek() {
+            if deadline > now {
+                break;
+            }
+            self.timers.pop();
+
+            let mut shell = BarShell::new(&mut messages);
+            self.widgets[ix].on_tick(&mut shell);
+            if shell.redraw_requested {
+                dirty.push(ix);
+            }
+
+            if let Some(interval) = self.widgets[ix].refresh_interval() {
+                self.timers.push(Reverse((now + interval, ix)));
+            }
+        }
+
+        if !dirty.is_empty() {
+            if let Err(e) = self.repaint(&dirty) {
+                error!("unable to redraw bar: {}", e);
+            }
+        }
+
+        messages
+    }
+
     /// Re-render all widgets in this status bar
     pub fn redraw(&mut self) -> Result<()> {
         let mut ctx = self.drw.context_for(self.id)?;
 
         ctx.color(&self.bg);
-        ctx.rectangle(0.0, 0.0, self.w as f64, self.h as f64);
+        ctx.rectangle(0.0, 0.0, self.w, self.h);
 
-        let extents = self.layout(&mut ctx)?;
-        for (wd, (w, _)) in self.widgets.iter_mut().zip(extents) {
+        let widths = self.layout(&mut ctx)?;
+        self.extents = cumulative_offsets(&widths, self.spacing);
+
+        for (wd, (w, _)) in self.widgets.iter_mut().zip(widths) {
             wd.draw(&mut ctx, w, self.h)?;
             ctx.translate(w + self.spacing, 0.0);
         }
@@ -78,6 +197,59 @@ impl<Ctx: DrawContext> StatusBar<Ctx> {
         Ok(())
     }
 
+    /// Repaint a single widget in place, having already established that its width has
+    /// not changed since the last full layout pass (so nothing to its right needs to move).
+    fn redraw_one(&mut self, ix: usize) -> Result<()> {
+        let (x, w) = self.extents[ix];
+        let mut ctx = self.drw.context_for(self.id)?;
+
+        ctx.save();
+        ctx.clip(x, 0.0, w, self.h);
+        ctx.color(&self.bg);
+        ctx.rectangle(x, 0.0, w, self.h);
+        ctx.translate(x, 0.0);
+        self.widgets[ix].draw(&mut ctx, w, self.h)?;
+        ctx.restore();
+
+        self.drw.flush();
+        Ok(())
+    }
+
+    /// Repaint `ix` and every widget after it, leaving everything to the left untouched.
+    /// Used when a dirty widget's width has changed, which shifts the layout of its
+    /// right-hand neighbours.
+    fn redraw_from(&mut self, ix: usize) -> Result<()> {
+        let mut ctx = self.drw.context_for(self.id)?;
+        let (x, _) = self.extents[ix];
+
+        ctx.save();
+        ctx.clip(x, 0.0, self.w - x, self.h);
+        ctx.color(&self.bg);
+        ctx.rectangle(x, 0.0, self.w - x, self.h);
+
+        let h = self.h;
+        let widths: Vec<(f64, f64)> = self.widgets[ix..]
+            .iter_mut()
+            .map(|w| w.current_extent(&mut ctx, h))
+            .collect::<Result<_>>()?;
+
+        ctx.translate(x, 0.0);
+        for (i, (w, _)) in widths.iter().enumerate() {
+            self.widgets[ix + i].draw(&mut ctx, *w, self.h)?;
+            ctx.translate(w + self.spacing, 0.0);
+        }
+        ctx.restore();
+
+        let mut x_offset = x;
+        for (i, (w, _)) in widths.iter().enumerate() {
+            self.extents[ix + i] = (x_offset, *w);
+            x_offset += w + self.spacing;
+        }
+
+        self.drw.flush();
+        Ok(())
+    }
+
     fn layout(&mut self, ctx: &mut dyn DrawContext) -> Result<Vec<(f64, f64)>> {
         let mut extents = Vec::with_capacity(self.widgets.len());
         for w in self.widgets.iter_mut() {
@@ -100,29 +272,125 @@ impl<Ctx: DrawContext> StatusBar<Ctx> {
         Ok(extents)
     }
 
-    fn redraw_if_needed(&mut self) {
-        if self.widgets.iter().any(|w| w.require_draw()) {
-            match self.redraw() {
-                Ok(_) => (),
-                Err(e) => error!("unable to redraw bar: {}", e),
+    /// Repaint exactly the widgets listed in `dirty`, falling back to a full redraw of
+    /// a widget and everything to its right when its width has changed (which shifts
+    /// its neighbours), or to a full bar redraw if we have no cached layout to diff
+    /// against yet or if the bar has greedy widgets whose padding the change affects.
+    fn repaint(&mut self, dirty: &[usize]) -> Result<()> {
+        if self.extents.len() != self.widgets.len() {
+            return self.redraw();
+        }
+
+        // Callers (e.g. `poll`, which ticks widgets in timer-deadline order rather than
+        // index order) may hand us `dirty` out of order. The `redraw_from`/`redraw`
+        // fallbacks below only repaint from the first resized index onward and then
+        // stop, so an out-of-order `dirty` would silently skip any lower-index widget
+        // whose own redraw was still pending.
+        let mut dirty = dirty.to_vec();
+        dirty.sort_unstable();
+
+        for &ix in dirty.iter() {
+            let (_, cached_w) = self.extents[ix];
+            let current_w = {
+                let mut ctx = self.drw.context_for(self.id)?;
+                self.widgets[ix].current_extent(&mut ctx, self.h)?.0
+            };
+
+            if (current_w - cached_w).abs() < WIDTH_EPSILON {
+                self.redraw_one(ix)?;
+            } else if self.greedy_indices.is_empty() {
+                // The width of this widget has changed which shifts everything to its
+                // right: repaint from here to the end of the bar and stop, the
+                // remaining dirty indices are covered by this pass.
+                self.redraw_from(ix)?;
+                break;
+            } else {
+                // `redraw_from` only re-lays-out the tail slice, but a greedy
+                // widget's fill-to-width padding depends on the *total* bar width,
+                // which this change just invalidated everywhere. Recomputing just
+                // the tail would silently drop that padding, so fall back to a full
+                // layout pass instead.
+                self.redraw()?;
+                break;
             }
         }
+
+        Ok(())
+    }
+
+    /// Once a hook has been dispatched to every widget via a fresh [BarShell], repaint
+    /// whatever was flagged as dirty and forward any published messages to the
+    /// WindowManager.
+    fn finish_dispatch(
+        &mut self,
+        dirty: &[usize],
+        messages: Vec<BarMessage>,
+        wm: &mut WindowManager,
+    ) {
+        if !dirty.is_empty() {
+            if let Err(e) = self.repaint(dirty) {
+                error!("unable to redraw bar: {}", e);
+            }
+        }
+
+        for msg in messages {
+            wm.handle_bar_message(msg);
+        }
+    }
+
+    // Whether this bar was already built against a screen of this size, used by
+    // [StatusBarSet] to decide if a bar needs to be torn down and recreated.
+    fn matches_screen(&self, sw: usize, sh: usize) -> bool {
+        (self.w - sw as f64).abs() < f64::EPSILON && self.sh == sh
+    }
+
+    // Explicitly tear down this bar's underlying dock window instead of relying on an
+    // implicit drop, so bars replaced or removed by [StatusBarSet::sync_screens] don't
+    // leak the window (and its colormap, for ARGB bars) on the X server.
+    pub(crate) fn destroy(&mut self) -> Result<()> {
+        self.drw.destroy_window(self.id)
     }
 }
 
+/// Turn a sequence of widget (width, height) pairs into (x_offset, width) pairs laid
+/// out left to right with `spacing` between each widget.
+fn cumulative_offsets(widths: &[(f64, f64)], spacing: f64) -> Vec<(f64, f64)> {
+    let mut x_offset = 0.0;
+    let mut offsets = Vec::with_capacity(widths.len());
+
+    for (w, _) in widths.iter() {
+        offsets.push((x_offset, *w));
+        x_offset += w + spacing;
+    }
+
+    offsets
+}
+
 impl<Ctx: DrawContext> Hook for StatusBar<Ctx> {
     fn new_client(&mut self, wm: &mut WindowManager, c: &mut Client) {
-        for w in self.widgets.iter_mut() {
-            w.new_client(wm, c);
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+        for (i, w) in self.widgets.iter_mut().enumerate() {
+            let mut shell = BarShell::new(&mut messages);
+            w.new_client(&mut shell, wm, c);
+            if shell.redraw_requested {
+                dirty.push(i);
+            }
         }
-        self.redraw_if_needed();
+        self.finish_dispatch(&dirty, messages, wm);
     }
 
     fn remove_client(&mut self, wm: &mut WindowManager, id: WinId) {
-        for w in self.widgets.iter_mut() {
-            w.remove_client(wm, id);
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+        for (i, w) in self.widgets.iter_mut().enumerate() {
+            let mut shell = BarShell::new(&mut messages);
+            w.remove_client(&mut shell, wm, id);
+            if shell.redraw_requested {
+                dirty.push(i);
+            }
         }
-        self.redraw_if_needed();
+        self.finish_dispatch(&dirty, messages, wm);
     }
 
     fn client_name_updated(
@@ -132,37 +400,695 @@ impl<Ctx: DrawContext> Hook for StatusBar<Ctx> {
         name: &str,
         is_root: bool,
     ) {
-        for w in self.widgets.iter_mut() {
-            w.client_name_updated(wm, id, name, is_root);
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+        for (i, w) in self.widgets.iter_mut().enumerate() {
+            let mut shell = BarShell::new(&mut messages);
+            w.client_name_updated(&mut shell, wm, id, name, is_root);
+            if shell.redraw_requested {
+                dirty.push(i);
+            }
         }
-        self.redraw_if_needed();
+        self.finish_dispatch(&dirty, messages, wm);
     }
 
     fn layout_change(&mut self, wm: &mut WindowManager, ws_ix: usize, s_ix: usize) {
-        for w in self.widgets.iter_mut() {
-            w.layout_change(wm, ws_ix, s_ix);
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+        for (i, w) in self.widgets.iter_mut().enumerate() {
+            let mut shell = BarShell::new(&mut messages);
+            w.layout_change(&mut shell, wm, ws_ix, s_ix);
+            if shell.redraw_requested {
+                dirty.push(i);
+            }
         }
-        self.redraw_if_needed();
+        self.finish_dispatch(&dirty, messages, wm);
     }
 
     fn workspace_change(&mut self, wm: &mut WindowManager, prev: usize, new: usize) {
-        for w in self.widgets.iter_mut() {
-            w.workspace_change(wm, prev, new);
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+        for (i, w) in self.widgets.iter_mut().enumerate() {
+            let mut shell = BarShell::new(&mut messages);
+            w.workspace_change(&mut shell, wm, prev, new);
+            if shell.redraw_requested {
+                dirty.push(i);
+            }
         }
-        self.redraw_if_needed();
+        self.finish_dispatch(&dirty, messages, wm);
     }
 
     fn screen_change(&mut self, wm: &mut WindowManager, ix: usize) {
-        for w in self.widgets.iter_mut() {
-            w.screen_change(wm, ix);
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+        for (i, w) in self.widgets.iter_mut().enumerate() {
+            let mut shell = BarShell::new(&mut messages);
+            w.screen_change(&mut shell, wm, ix);
+            if shell.redraw_requested {
+                dirty.push(i);
+            }
         }
-        self.redraw_if_needed();
+        self.finish_dispatch(&dirty, messages, wm);
     }
 
     fn focus_change(&mut self, wm: &mut WindowManager, id: WinId) {
-        for w in self.widgets.iter_mut() {
-            w.focus_change(wm, id);
+        let mut messages = Vec::new();
+        let mut dirty = Vec::new();
+        for (i, w) in self.widgets.iter_mut().enumerate() {
+            let mut shell = BarShell::new(&mut messages);
+            w.focus_change(&mut shell, wm, id);
+            if shell.redraw_requested {
+                dirty.push(i);
+            }
+        }
+        self.finish_dispatch(&dirty, messages, wm);
+    }
+}
+
+/// Owns one [StatusBar] per connected screen and keeps them in sync with the current
+/// RandR layout, so a multi-monitor setup doesn't need one bar created and positioned
+/// by hand per output.
+///
+/// `make_drw` opens a fresh [Draw] handle for each bar's window and `make_widgets`
+/// builds the widget set for a given screen index, letting different screens carry
+/// different widgets (e.g. a workspace widget only on the primary monitor).
+pub struct StatusBarSet<Ctx> {
+    bars: Vec<StatusBar<Ctx>>,
+    make_drw: Box<dyn Fn() -> Result<Box<dyn Draw<Ctx = Ctx>>>>,
+    make_widgets: Box<dyn Fn(usize) -> Vec<Box<dyn Widget>>>,
+    position: Position,
+    spacing: f64,
+    h: usize,
+    bg: Color,
+    fonts: Vec<String>,
+    // A connection kept open purely to listen for RandR screen-change notifications,
+    // independent of whatever connection(s) back the bars themselves: see
+    // [StatusBarSet::poll_randr].
+    monitor: Box<dyn Draw<Ctx = Ctx>>,
+}
+
+impl<Ctx: DrawContext> StatusBarSet<Ctx> {
+    /// Try to initialise one bar per currently connected screen. Can fail if we are
+    /// unable to create any of the underlying bar windows.
+    pub fn try_new(
+        make_drw: impl Fn() -> Result<Box<dyn Draw<Ctx = Ctx>>> + 'static,
+        position: Position,
+        spacing: f64,
+        h: usize,
+        bg: impl Into<Color>,
+        fonts: &[&str],
+        make_widgets: impl Fn(usize) -> Vec<Box<dyn Widget>> + 'static,
+    ) -> Result<Self> {
+        let monitor = make_drw()?;
+
+        let mut set = Self {
+            bars: vec![],
+            make_drw: Box::new(make_drw),
+            make_widgets: Box::new(make_widgets),
+            position,
+            spacing,
+            h,
+            bg: bg.into(),
+            fonts: fonts.iter().map(|f| f.to_string()).collect(),
+            monitor,
+        };
+
+        set.sync_screens()?;
+
+        Ok(set)
+    }
+
+    /// Check for a pending RandR screen-change notification and resync the bar set if
+    /// one has arrived. Call this whenever the `monitor` connection's underlying X
+    /// connection becomes readable, e.g. from the WindowManager's select/poll loop.
+    /// Unlike [Hook::screen_change] (which every [StatusBar] also reacts to, driven by
+    /// the WindowManager's own internal screen/workspace mapping changes), this is the
+    /// path actually driven by X11 output hotplug/resize events.
+    pub fn poll_randr(&mut self) -> Result<()> {
+        if self.monitor.poll_randr_change()? {
+            self.sync_screens()?;
+        }
+
+        Ok(())
+    }
+
+    /// Re-query the current screen layout and destroy/recreate or drop any bar whose
+    /// screen has disappeared, been resized, or newly appeared. Call this whenever the
+    /// set of connected screens may have changed; [StatusBarSet::poll_randr] is the
+    /// RandR-driven trigger for this, but it's also called directly from
+    /// [Hook::screen_change].
+    pub fn sync_screens(&mut self) -> Result<()> {
+        let mut probe = (self.make_drw)()?;
+        let n = probe.n_screens()?;
+        let mut sizes = Vec::with_capacity(n);
+        for ix in 0..n {
+            sizes.push(probe.screen_size(ix)?);
+        }
+
+        // Explicitly tear down the dock windows for any screens that have
+        // disappeared rather than relying on an implicit drop of the `StatusBar` to
+        // clean up its window and (for ARGB bars) colormap.
+        for mut bar in self.bars.drain(n.min(self.bars.len())..) {
+            if let Err(e) = bar.destroy() {
+                error!("unable to destroy status bar window: {}", e);
+            }
+        }
+
+        // The connection we used to probe the screen layout would otherwise just be
+        // dropped: reuse it for the first bar we need to (re)build instead of
+        // opening another one straight away.
+        let mut probe = Some(probe);
+
+        for (ix, (sw, sh)) in sizes.into_iter().enumerate() {
+            let up_to_date = self
+                .bars
+                .get(ix)
+                .is_some_and(|bar| bar.matches_screen(sw, sh));
+
+            if up_to_date {
+                continue;
+            }
+
+            let drw = match probe.take() {
+                Some(drw) => drw,
+                None => (self.make_drw)()?,
+            };
+            let widgets = (self.make_widgets)(ix);
+            let fonts: Vec<&str> = self.fonts.iter().map(String::as_str).collect();
+            let bar = StatusBar::try_new(
+                drw,
+                self.position,
+                self.spacing,
+                ix,
+                self.h,
+                self.bg,
+                &fonts,
+                widgets,
+            )?;
+
+            if ix < self.bars.len() {
+                let mut old = std::mem::replace(&mut self.bars[ix], bar);
+                if let Err(e) = old.destroy() {
+                    error!("unable to destroy status bar window: {}", e);
+                }
+            } else {
+                self.bars.push(bar);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<Ctx: DrawContext> Hook for StatusBarSet<Ctx> {
+    fn new_client(&mut self, wm: &mut WindowManager, c: &mut Client) {
+        for bar in self.bars.iter_mut() {
+            bar.new_client(wm, c);
+        }
+    }
+
+    fn remove_client(&mut self, wm: &mut WindowManager, id: WinId) {
+        for bar in self.bars.iter_mut() {
+            bar.remove_client(wm, id);
+        }
+    }
+
+    fn client_name_updated(
+        &mut self,
+        wm: &mut WindowManager,
+        id: WinId,
+        name: &str,
+        is_root: bool,
+    ) {
+        for bar in self.bars.iter_mut() {
+            bar.client_name_updated(wm, id, name, is_root);
+        }
+    }
+
+    fn layout_change(&mut self, wm: &mut WindowManager, ws_ix: usize, s_ix: usize) {
+        for bar in self.bars.iter_mut() {
+            bar.layout_change(wm, ws_ix, s_ix);
+        }
+    }
+
+    fn workspace_change(&mut self, wm: &mut WindowManager, prev: usize, new: usize) {
+        for bar in self.bars.iter_mut() {
+            bar.workspace_change(wm, prev, new);
+        }
+    }
+
+    fn screen_change(&mut self, wm: &mut WindowManager, ix: usize) {
+        if let Err(e) = self.sync_screens() {
+            error!(
+                "unable to sync status bars to the current screen layout: {}",
+                e
+            );
+        }
+
+        for bar in self.bars.iter_mut() {
+            bar.screen_change(wm, ix);
+        }
+    }
+
+    fn focus_change(&mut self, wm: &mut WindowManager, id: WinId) {
+        for bar in self.bars.iter_mut() {
+            bar.focus_change(wm, id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    #[test]
+    fn cumulative_offsets_lays_out_widgets_left_to_right_with_spacing() {
+        let widths = [(10.0, 1.0), (20.0, 1.0), (5.0, 1.0)];
+
+        let offsets = cumulative_offsets(&widths, 2.0);
+
+        assert_eq!(offsets, vec![(0.0, 10.0), (12.0, 20.0), (34.0, 5.0)]);
+    }
+
+    #[test]
+    fn cumulative_offsets_of_empty_widgets_is_empty() {
+        assert_eq!(cumulative_offsets(&[], 2.0), Vec::new());
+    }
+
+    #[test]
+    fn timer_heap_pops_deadlines_soonest_first() {
+        let now = Instant::now();
+        let mut timers: BinaryHeap<Reverse<(Instant, usize)>> = BinaryHeap::new();
+
+        timers.push(Reverse((now + Duration::from_secs(5), 0)));
+        timers.push(Reverse((now + Duration::from_secs(1), 1)));
+        timers.push(Reverse((now + Duration::from_secs(3), 2)));
+
+        let order: Vec<usize> = std::iter::from_fn(|| timers.pop().map(|Reverse((_, ix))| ix))
+            .collect();
+
+        assert_eq!(order, vec![1, 2, 0]);
+    }
+
+    struct TickingWidget {
+        interval: Duration,
+        next_interval: Option<Duration>,
+        ticks: Rc<RefCell<Vec<&'static str>>>,
+        tag: &'static str,
+    }
+
+    impl Widget for TickingWidget {
+        fn current_extent(&mut self, _ctx: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)> {
+            Ok((10.0, h))
+        }
+
+        fn draw(&mut self, _ctx: &mut dyn DrawContext, _w: f64, _h: f64) -> Result<()> {
+            Ok(())
+        }
+
+        fn refresh_interval(&self) -> Option<Duration> {
+            Some(self.interval)
+        }
+
+        fn on_tick(&mut self, shell: &mut BarShell) {
+            self.ticks.borrow_mut().push(self.tag);
+            shell.request_redraw();
+            if let Some(next) = self.next_interval.take() {
+                self.interval = next;
+            }
+        }
+    }
+
+    #[test]
+    fn poll_ticks_due_widgets_and_reschedules_against_their_current_interval() {
+        let ticks: Rc<RefCell<Vec<&'static str>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let widgets: Vec<Box<dyn Widget>> = vec![
+            Box::new(TickingWidget {
+                interval: Duration::from_secs(1),
+                next_interval: None,
+                ticks: ticks.clone(),
+                tag: "clock",
+            }),
+            Box::new(TickingWidget {
+                interval: Duration::from_secs(3),
+                next_interval: Some(Duration::from_secs(30)),
+                ticks: ticks.clone(),
+                tag: "network",
+            }),
+        ];
+        let mut bar = test_bar(widgets);
+
+        // Make both widgets due right now, with the higher-index "network" widget due
+        // first - the deadline/index inversion that used to make `repaint` drop a
+        // pending redraw (see the out-of-order `dirty` test below).
+        let now = Instant::now();
+        bar.timers = BinaryHeap::new();
+        bar.timers.push(Reverse((now - Duration::from_millis(2), 1)));
+        bar.timers.push(Reverse((now - Duration::from_millis(1), 0)));
+
+        bar.poll();
+
+        assert_eq!(*ticks.borrow(), vec!["network", "clock"]);
+
+        // The network widget's on_tick asked for a new 30s interval: its rescheduled
+        // deadline must reflect that, not the 3s interval it started with.
+        let network_deadline = bar
+            .timers
+            .clone()
+            .into_sorted_vec()
+            .into_iter()
+            .find_map(|Reverse((deadline, ix))| (ix == 1).then_some(deadline))
+            .unwrap();
+        assert!(network_deadline >= now + Duration::from_secs(29));
+    }
+
+    struct FixedWidthWidget {
+        width: f64,
+        calls: Rc<RefCell<Vec<&'static str>>>,
+        tag: &'static str,
+    }
+
+    impl Widget for FixedWidthWidget {
+        fn current_extent(&mut self, _ctx: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)> {
+            Ok((self.width, h))
+        }
+
+        fn draw(&mut self, _ctx: &mut dyn DrawContext, _w: f64, _h: f64) -> Result<()> {
+            self.calls.borrow_mut().push(self.tag);
+            Ok(())
         }
-        self.redraw_if_needed();
+    }
+
+    struct NoopDraw;
+
+    impl Draw for NoopDraw {
+        type Ctx = NoopDrawContext;
+
+        fn n_screens(&mut self) -> Result<usize> {
+            Ok(1)
+        }
+
+        fn screen_size(&mut self, _screen_index: usize) -> Result<(usize, usize)> {
+            Ok((100, 20))
+        }
+
+        fn new_window(
+            &mut self,
+            _ty: &WindowType,
+            _x: usize,
+            _y: usize,
+            _w: usize,
+            _h: usize,
+            _transparent: bool,
+        ) -> Result<WinId> {
+            Ok(1)
+        }
+
+        fn destroy_window(&mut self, _id: WinId) -> Result<()> {
+            Ok(())
+        }
+
+        fn poll_randr_change(&mut self) -> Result<bool> {
+            Ok(false)
+        }
+
+        fn register_font(&mut self, _font_name: &str) {}
+
+        fn context_for(&self, _id: WinId) -> Result<Self::Ctx> {
+            Ok(NoopDrawContext)
+        }
+
+        fn flush(&self) {}
+    }
+
+    struct NoopDrawContext;
+
+    impl DrawContext for NoopDrawContext {
+        fn color(&mut self, _color: &Color) {}
+        fn rectangle(&mut self, _x: f64, _y: f64, _w: f64, _h: f64) {}
+        fn translate(&mut self, _dx: f64, _dy: f64) {}
+        fn save(&mut self) {}
+        fn restore(&mut self) {}
+        fn clip(&mut self, _x: f64, _y: f64, _w: f64, _h: f64) {}
+    }
+
+    fn test_bar(widgets: Vec<Box<dyn Widget>>) -> StatusBar<NoopDrawContext> {
+        StatusBar::try_new(
+            Box::new(NoopDraw),
+            Position::Top,
+            2.0,
+            0,
+            20,
+            (0.0, 0.0, 0.0),
+            &[],
+            widgets,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn repaint_redraws_only_the_dirty_widget_when_its_width_is_unchanged() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let widgets: Vec<Box<dyn Widget>> = vec![
+            Box::new(FixedWidthWidget {
+                width: 10.0,
+                calls: calls.clone(),
+                tag: "left",
+            }),
+            Box::new(FixedWidthWidget {
+                width: 10.0,
+                calls: calls.clone(),
+                tag: "right",
+            }),
+        ];
+        let mut bar = test_bar(widgets);
+        calls.borrow_mut().clear();
+
+        bar.repaint(&[1]).unwrap();
+
+        assert_eq!(*calls.borrow(), vec!["right"]);
+    }
+
+    #[test]
+    fn repaint_redraws_from_the_resized_widget_onwards() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let widgets: Vec<Box<dyn Widget>> = vec![
+            Box::new(FixedWidthWidget {
+                width: 10.0,
+                calls: calls.clone(),
+                tag: "left",
+            }),
+            Box::new(FixedWidthWidget {
+                width: 15.0,
+                calls: calls.clone(),
+                tag: "middle",
+            }),
+            Box::new(FixedWidthWidget {
+                width: 10.0,
+                calls: calls.clone(),
+                tag: "right",
+            }),
+        ];
+        let mut bar = test_bar(widgets);
+        // Simulate the middle widget having grown since the last layout pass.
+        bar.widgets[1] = Box::new(FixedWidthWidget {
+            width: 25.0,
+            calls: calls.clone(),
+            tag: "middle",
+        });
+        calls.borrow_mut().clear();
+
+        bar.repaint(&[1]).unwrap();
+
+        assert_eq!(*calls.borrow(), vec!["middle", "right"]);
+    }
+
+    #[test]
+    fn repaint_does_not_drop_a_lower_index_widget_when_dirty_is_out_of_order() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let widgets: Vec<Box<dyn Widget>> = vec![
+            Box::new(FixedWidthWidget {
+                width: 10.0,
+                calls: calls.clone(),
+                tag: "left",
+            }),
+            Box::new(FixedWidthWidget {
+                width: 15.0,
+                calls: calls.clone(),
+                tag: "middle",
+            }),
+            Box::new(FixedWidthWidget {
+                width: 10.0,
+                calls: calls.clone(),
+                tag: "right",
+            }),
+        ];
+        let mut bar = test_bar(widgets);
+        // Simulate the middle widget having grown since the last layout pass, with the
+        // untouched "right" widget's deadline popping (and so appearing in `dirty`)
+        // before its own, as can happen once two widgets' timer deadlines invert
+        // relative to their index order.
+        bar.widgets[1] = Box::new(FixedWidthWidget {
+            width: 25.0,
+            calls: calls.clone(),
+            tag: "middle",
+        });
+        calls.borrow_mut().clear();
+
+        bar.repaint(&[2, 1]).unwrap();
+
+        assert_eq!(*calls.borrow(), vec!["middle", "right"]);
+    }
+
+    #[test]
+    fn matches_screen_is_true_only_for_the_exact_geometry_the_bar_was_built_with() {
+        let bar = test_bar(vec![]);
+
+        assert!(bar.matches_screen(100, 20));
+        assert!(!bar.matches_screen(100, 30));
+        assert!(!bar.matches_screen(200, 20));
+    }
+
+    struct MultiScreenDraw {
+        screens: Rc<RefCell<Vec<(usize, usize)>>>,
+        destroyed: Rc<RefCell<Vec<WinId>>>,
+        next_id: Rc<RefCell<WinId>>,
+        // Shared across every `Draw` handle `make_drw` produces, so tests can flag a
+        // RandR change as if it had arrived on whichever connection is listening for
+        // it (here, `StatusBarSet`'s dedicated `monitor` handle).
+        randr_pending: Rc<RefCell<bool>>,
+    }
+
+    impl Draw for MultiScreenDraw {
+        type Ctx = NoopDrawContext;
+
+        fn n_screens(&mut self) -> Result<usize> {
+            Ok(self.screens.borrow().len())
+        }
+
+        fn screen_size(&mut self, screen_index: usize) -> Result<(usize, usize)> {
+            Ok(self.screens.borrow()[screen_index])
+        }
+
+        fn new_window(
+            &mut self,
+            _ty: &WindowType,
+            _x: usize,
+            _y: usize,
+            _w: usize,
+            _h: usize,
+            _transparent: bool,
+        ) -> Result<WinId> {
+            let mut id = self.next_id.borrow_mut();
+            *id += 1;
+            Ok(*id)
+        }
+
+        fn destroy_window(&mut self, id: WinId) -> Result<()> {
+            self.destroyed.borrow_mut().push(id);
+            Ok(())
+        }
+
+        fn poll_randr_change(&mut self) -> Result<bool> {
+            Ok(std::mem::take(&mut *self.randr_pending.borrow_mut()))
+        }
+
+        fn register_font(&mut self, _font_name: &str) {}
+
+        fn context_for(&self, _id: WinId) -> Result<Self::Ctx> {
+            Ok(NoopDrawContext)
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn sync_screens_creates_and_tears_down_bars_as_the_screen_count_changes() {
+        let screens = Rc::new(RefCell::new(vec![(100, 20), (200, 20)]));
+        let destroyed = Rc::new(RefCell::new(Vec::new()));
+        let next_id = Rc::new(RefCell::new(0));
+        let randr_pending = Rc::new(RefCell::new(false));
+
+        let make_screens = screens.clone();
+        let make_destroyed = destroyed.clone();
+        let make_next_id = next_id.clone();
+        let make_randr_pending = randr_pending.clone();
+        let mut set = StatusBarSet::try_new(
+            move || {
+                Ok(Box::new(MultiScreenDraw {
+                    screens: make_screens.clone(),
+                    destroyed: make_destroyed.clone(),
+                    next_id: make_next_id.clone(),
+                    randr_pending: make_randr_pending.clone(),
+                }) as Box<dyn Draw<Ctx = NoopDrawContext>>)
+            },
+            Position::Top,
+            2.0,
+            20,
+            (0.0, 0.0, 0.0),
+            &[],
+            |_ix| vec![],
+        )
+        .unwrap();
+
+        assert_eq!(set.bars.len(), 2);
+
+        // A screen disappearing should tear down its bar's window.
+        screens.borrow_mut().pop();
+        set.sync_screens().unwrap();
+
+        assert_eq!(set.bars.len(), 1);
+        assert_eq!(destroyed.borrow().len(), 1);
+
+        // A resized screen should be torn down and rebuilt rather than left stale.
+        screens.borrow_mut()[0].0 = 150;
+        set.sync_screens().unwrap();
+
+        assert_eq!(set.bars.len(), 1);
+        assert_eq!(destroyed.borrow().len(), 2);
+        assert!(set.bars[0].matches_screen(150, 20));
+    }
+
+    #[test]
+    fn poll_randr_resyncs_the_bar_set_only_when_a_change_was_observed() {
+        let screens = Rc::new(RefCell::new(vec![(100, 20)]));
+        let destroyed = Rc::new(RefCell::new(Vec::new()));
+        let next_id = Rc::new(RefCell::new(0));
+        let randr_pending = Rc::new(RefCell::new(false));
+
+        let make_screens = screens.clone();
+        let make_destroyed = destroyed.clone();
+        let make_next_id = next_id.clone();
+        let make_randr_pending = randr_pending.clone();
+        let mut set = StatusBarSet::try_new(
+            move || {
+                Ok(Box::new(MultiScreenDraw {
+                    screens: make_screens.clone(),
+                    destroyed: make_destroyed.clone(),
+                    next_id: make_next_id.clone(),
+                    randr_pending: make_randr_pending.clone(),
+                }) as Box<dyn Draw<Ctx = NoopDrawContext>>)
+            },
+            Position::Top,
+            2.0,
+            20,
+            (0.0, 0.0, 0.0),
+            &[],
+            |_ix| vec![],
+        )
+        .unwrap();
+
+        // A second monitor appearing without a RandR notification shouldn't be picked
+        // up: `poll_randr` only resyncs when the dedicated monitor connection actually
+        // observed a screen-change event.
+        screens.borrow_mut().push((200, 20));
+        set.poll_randr().unwrap();
+        assert_eq!(set.bars.len(), 1);
+
+        *randr_pending.borrow_mut() = true;
+        set.poll_randr().unwrap();
+        assert_eq!(set.bars.len(), 2);
     }
 }
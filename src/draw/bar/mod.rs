@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+mod bar;
+
+pub use bar::{BarMessage, BarShell, Position, StatusBar, StatusBarSet};
@@ -0,0 +1,149 @@
+//! Rendering status bars and the windows that host them
+pub mod bar;
+mod widget;
+mod xcb;
+
+pub use widget::Widget;
+pub use xcb::{XcbDraw, XcbDrawContext};
+
+use crate::{data_types::WinId, Result};
+
+/// The type of an X11 window, used to set the appropriate `_NET_WM_WINDOW_TYPE` hint
+pub enum WindowType {
+    /// A status bar / dock window that should be excluded from normal tiling
+    Dock,
+    /// A regular top level client window
+    Normal,
+}
+
+/// An RGBA color. Components are normalised floats in the range `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    r: f64,
+    g: f64,
+    b: f64,
+    a: f64,
+}
+
+impl Color {
+    /// Construct a new fully opaque color from RGB components
+    pub fn new(r: f64, g: f64, b: f64) -> Self {
+        Self { r, g, b, a: 1.0 }
+    }
+
+    /// Construct a new color with an explicit alpha component
+    pub fn new_with_alpha(r: f64, g: f64, b: f64, a: f64) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// The red component of this color
+    pub fn red(&self) -> f64 {
+        self.r
+    }
+
+    /// The green component of this color
+    pub fn green(&self) -> f64 {
+        self.g
+    }
+
+    /// The blue component of this color
+    pub fn blue(&self) -> f64 {
+        self.b
+    }
+
+    /// The alpha component of this color. `1.0` is fully opaque.
+    pub fn alpha(&self) -> f64 {
+        self.a
+    }
+}
+
+impl From<(f64, f64, f64)> for Color {
+    fn from((r, g, b): (f64, f64, f64)) -> Self {
+        Color::new(r, g, b)
+    }
+}
+
+impl From<(f64, f64, f64, f64)> for Color {
+    fn from((r, g, b, a): (f64, f64, f64, f64)) -> Self {
+        Color::new_with_alpha(r, g, b, a)
+    }
+}
+
+/// A handle capable of creating and rendering to windows. Implementors own the
+/// underlying connection to the X server (and any supporting libraries such as
+/// cairo/pango used to actually paint).
+pub trait Draw {
+    /// The [DrawContext] implementation used to paint onto windows created by this
+    /// `Draw`.
+    type Ctx: DrawContext;
+
+    /// The number of screens currently known to the X server (i.e. the number of
+    /// RandR CRTCs currently driving at least one output, or the number of X screens
+    /// when RandR is unavailable).
+    fn n_screens(&mut self) -> Result<usize>;
+
+    /// The `(width, height)` of the screen at `screen_index`
+    fn screen_size(&mut self, screen_index: usize) -> Result<(usize, usize)>;
+
+    /// Create a new top level window of the given type and geometry. When
+    /// `transparent` is set the window is created against a 32-bit TrueColor ARGB
+    /// visual (and matching colormap) instead of the default visual, so that any
+    /// alpha painted into it blends with whatever a running compositor places
+    /// behind it.
+    fn new_window(
+        &mut self,
+        ty: &WindowType,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        transparent: bool,
+    ) -> Result<WinId>;
+
+    /// Destroy a window previously created with [Draw::new_window], freeing any
+    /// associated server-side resources (colormap, graphics context, ...).
+    fn destroy_window(&mut self, id: WinId) -> Result<()>;
+
+    /// Check (without blocking) whether a RandR screen-change notification has arrived
+    /// since the last call, indicating that [Draw::n_screens]/[Draw::screen_size] may
+    /// now return different results. Call this whenever the underlying X connection
+    /// becomes readable. This is independent of [crate::hooks::Hook::screen_change],
+    /// which instead fires in response to the WindowManager's own internal
+    /// screen/workspace mapping changes rather than an actual X11 output hotplug or
+    /// resize.
+    fn poll_randr_change(&mut self) -> Result<bool>;
+
+    /// Register a font by name so that it can later be used by a [DrawContext]
+    fn register_font(&mut self, font_name: &str);
+
+    /// Obtain a [DrawContext] for painting onto the given window
+    fn context_for(&self, id: WinId) -> Result<Self::Ctx>;
+
+    /// Flush all pending requests to the X server
+    fn flush(&self);
+}
+
+/// A surface that widgets and [bar::StatusBar] paint onto.
+pub trait DrawContext {
+    /// Set the active paint color for subsequent `rectangle`/text calls
+    fn color(&mut self, color: &Color);
+
+    /// Fill a `w`x`h` rectangle with the current color, relative to the current
+    /// translation
+    fn rectangle(&mut self, x: f64, y: f64, w: f64, h: f64);
+
+    /// Shift the coordinate origin used by subsequent draw calls
+    fn translate(&mut self, dx: f64, dy: f64);
+
+    /// Push the current transform/clip state onto an internal stack so that it can
+    /// later be restored with [DrawContext::restore]
+    fn save(&mut self);
+
+    /// Pop the most recently [DrawContext::save]d transform/clip state, discarding
+    /// anything pushed on top of it
+    fn restore(&mut self);
+
+    /// Restrict subsequent painting to the given rectangle, relative to the current
+    /// translation, until the next [DrawContext::restore]
+    fn clip(&mut self, x: f64, y: f64, w: f64, h: f64);
+}
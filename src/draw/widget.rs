@@ -0,0 +1,72 @@
+//! The `Widget` trait implemented by everything that can be placed in a status bar
+use crate::{
+    client::Client,
+    data_types::WinId,
+    draw::{bar::BarShell, DrawContext},
+    Result, WindowManager,
+};
+use std::time::Duration;
+
+/// A single element of a [crate::draw::bar::StatusBar]
+pub trait Widget {
+    /// The `(width, height)` this widget would like to occupy given the bar's height.
+    /// Called on every layout pass, so implementations should be cheap.
+    fn current_extent(&mut self, ctx: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)>;
+
+    /// Paint this widget into the `w`x`h` region at the context's current
+    /// translation
+    fn draw(&mut self, ctx: &mut dyn DrawContext, w: f64, h: f64) -> Result<()>;
+
+    /// How often this widget should be ticked via [Widget::on_tick], if at all.
+    /// Widgets that only update in response to hooks should leave this as `None`.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Called on the schedule returned by [Widget::refresh_interval]. Implementations
+    /// that change their visual state should call `shell.request_redraw()`.
+    fn on_tick(&mut self, _shell: &mut BarShell) {}
+
+    /// Called whenever a new client is picked up by the WindowManager
+    fn new_client(&mut self, _shell: &mut BarShell, _wm: &mut WindowManager, _c: &mut Client) {}
+
+    /// Called whenever a client is removed from the WindowManager
+    fn remove_client(&mut self, _shell: &mut BarShell, _wm: &mut WindowManager, _id: WinId) {}
+
+    /// Called whenever a client's window title changes
+    fn client_name_updated(
+        &mut self,
+        _shell: &mut BarShell,
+        _wm: &mut WindowManager,
+        _id: WinId,
+        _name: &str,
+        _is_root: bool,
+    ) {
+    }
+
+    /// Called whenever the active layout changes for a workspace on a given screen
+    fn layout_change(
+        &mut self,
+        _shell: &mut BarShell,
+        _wm: &mut WindowManager,
+        _ws_ix: usize,
+        _s_ix: usize,
+    ) {
+    }
+
+    /// Called whenever the focused workspace changes
+    fn workspace_change(
+        &mut self,
+        _shell: &mut BarShell,
+        _wm: &mut WindowManager,
+        _prev: usize,
+        _new: usize,
+    ) {
+    }
+
+    /// Called whenever the focused screen changes
+    fn screen_change(&mut self, _shell: &mut BarShell, _wm: &mut WindowManager, _ix: usize) {}
+
+    /// Called whenever focus moves to a different client
+    fn focus_change(&mut self, _shell: &mut BarShell, _wm: &mut WindowManager, _id: WinId) {}
+}
@@ -0,0 +1,336 @@
+//! An XCB + cairo backed implementation of [Draw] and [DrawContext]
+//!
+//! Requires the `xcb` crate's `randr` cargo feature, used here to report actual RandR
+//! monitor geometry instead of xcb's legacy per-connection X11 "screen" count (which is
+//! almost always `1` on a modern Xorg setup regardless of how many monitors are
+//! plugged in).
+use crate::{
+    data_types::WinId,
+    draw::{Color, Draw, DrawContext, WindowType},
+    PenroseError, Result,
+};
+use std::{collections::HashMap, rc::Rc};
+
+// Depth of a 32-bit TrueColor ARGB visual, as opposed to the usual 24-bit depth used
+// for opaque windows.
+const ARGB_DEPTH: u8 = 32;
+
+struct WindowState {
+    surface: cairo::XCBSurface,
+    transparent: bool,
+    // Only set for ARGB windows, which are created against a dedicated colormap that
+    // must be explicitly freed: the default colormap is shared and must never be.
+    colormap: Option<xcb::Colormap>,
+}
+
+/// An X11 `Draw` implementation using an XCB connection for window management and
+/// cairo (via its XCB surface backend) for actual rendering.
+pub struct XcbDraw {
+    conn: Rc<xcb::Connection>,
+    screen_num: i32,
+    fonts: Vec<String>,
+    windows: HashMap<WinId, WindowState>,
+    // The RandR extension's event base, used to recognise its events among the
+    // connection's generic events. `None` if the X server doesn't advertise RandR, in
+    // which case we fall back to treating xcb's legacy X11 screens as monitors.
+    randr_first_event: Option<u8>,
+}
+
+impl XcbDraw {
+    /// Open a new connection to the X server named by the `DISPLAY` environment
+    /// variable
+    pub fn new() -> Result<Self> {
+        let (conn, screen_num) =
+            xcb::Connection::connect(None).map_err(|e| PenroseError::X11(e.to_string()))?;
+
+        let randr_first_event = conn
+            .get_extension_data(xcb::randr::id())
+            .map(|data| data.first_event());
+
+        if randr_first_event.is_some() {
+            let root = conn
+                .get_setup()
+                .roots()
+                .nth(screen_num as usize)
+                .expect("a valid default screen")
+                .root();
+            xcb::randr::select_input(&conn, root, xcb::randr::NOTIFY_MASK_SCREEN_CHANGE as u16);
+            conn.flush();
+        }
+
+        Ok(Self {
+            conn: Rc::new(conn),
+            screen_num,
+            fonts: vec![],
+            windows: HashMap::new(),
+            randr_first_event,
+        })
+    }
+
+    fn screen(&self) -> xcb::Screen<'_> {
+        self.conn
+            .get_setup()
+            .roots()
+            .nth(self.screen_num as usize)
+            .expect("a valid default screen")
+    }
+
+    // The CRTCs that currently have an output attached, i.e. the monitors RandR
+    // actually considers connected, as opposed to a CRTC that exists but is unused.
+    fn active_crtcs(&self) -> Result<Vec<xcb::randr::GetCrtcInfoReply>> {
+        let root = self.screen().root();
+        let resources = xcb::randr::get_screen_resources_current(&self.conn, root)
+            .get_reply()
+            .map_err(|e| {
+                PenroseError::X11(format!("unable to query RandR screen resources: {}", e))
+            })?;
+
+        let timestamp = resources.config_timestamp();
+        let mut crtcs = Vec::new();
+        for &crtc in resources.crtcs() {
+            let info = xcb::randr::get_crtc_info(&self.conn, crtc, timestamp)
+                .get_reply()
+                .map_err(|e| PenroseError::X11(format!("unable to query RandR crtc: {}", e)))?;
+
+            if !info.outputs().is_empty() {
+                crtcs.push(info);
+            }
+        }
+
+        Ok(crtcs)
+    }
+
+    // Search the depths advertised by the default screen for a 32-bit TrueColor
+    // visual.
+    fn find_argb_visual(&self) -> Result<xcb::Visualtype> {
+        self.screen()
+            .allowed_depths()
+            .find(|d| d.depth() == ARGB_DEPTH)
+            .and_then(|d| d.visuals().find(|v| v.class() == xcb::VISUAL_CLASS_TRUE_COLOR as u8))
+            .ok_or_else(|| {
+                PenroseError::X11("no 32-bit TrueColor visual available".into())
+            })
+    }
+
+    // cairo needs the full `xcb_visualtype_t` for whichever visual we handed to
+    // `create_window`, not just its numeric id, so look it up among the depths the
+    // default screen advertises.
+    fn find_visual(&self, visual_id: xcb::Visualid) -> Result<xcb::Visualtype> {
+        self.screen()
+            .allowed_depths()
+            .flat_map(|d| d.visuals())
+            .find(|v| v.visual_id() == visual_id)
+            .ok_or_else(|| PenroseError::X11(format!("no such visual: {}", visual_id)))
+    }
+}
+
+impl Draw for XcbDraw {
+    type Ctx = XcbDrawContext;
+
+    fn n_screens(&mut self) -> Result<usize> {
+        if self.randr_first_event.is_some() {
+            Ok(self.active_crtcs()?.len())
+        } else {
+            Ok(self.conn.get_setup().roots().count())
+        }
+    }
+
+    fn screen_size(&mut self, screen_index: usize) -> Result<(usize, usize)> {
+        if self.randr_first_event.is_some() {
+            let crtcs = self.active_crtcs()?;
+            let info = crtcs
+                .get(screen_index)
+                .ok_or_else(|| PenroseError::X11(format!("no such screen: {}", screen_index)))?;
+
+            Ok((info.width() as usize, info.height() as usize))
+        } else {
+            let screen = self
+                .conn
+                .get_setup()
+                .roots()
+                .nth(screen_index)
+                .ok_or_else(|| PenroseError::X11(format!("no such screen: {}", screen_index)))?;
+
+            Ok((screen.width_in_pixels() as usize, screen.height_in_pixels() as usize))
+        }
+    }
+
+    fn new_window(
+        &mut self,
+        _ty: &WindowType,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        transparent: bool,
+    ) -> Result<WinId> {
+        let screen = self.screen();
+        let id = self.conn.generate_id();
+
+        let (depth, visual_id, colormap) = if transparent {
+            let visual = self.find_argb_visual()?;
+            let colormap = self.conn.generate_id();
+            xcb::create_colormap(
+                &self.conn,
+                xcb::COLORMAP_ALLOC_NONE as u8,
+                colormap,
+                screen.root(),
+                visual.visual_id(),
+            );
+            (ARGB_DEPTH, visual.visual_id(), Some(colormap))
+        } else {
+            (screen.root_depth(), screen.root_visual(), None)
+        };
+        let mut visual = self.find_visual(visual_id)?;
+
+        // A non-default colormap (and an explicit border pixel) is required by the
+        // X server whenever the window's depth doesn't match its parent's.
+        let mut values = vec![(xcb::CW_BORDER_PIXEL, 0), (xcb::CW_EVENT_MASK, 0)];
+        if let Some(cmap) = colormap {
+            values.push((xcb::CW_COLORMAP, cmap));
+        }
+
+        xcb::create_window(
+            &self.conn,
+            depth,
+            id,
+            screen.root(),
+            x as i16,
+            y as i16,
+            w as u16,
+            h as u16,
+            0,
+            xcb::WINDOW_CLASS_INPUT_OUTPUT as u16,
+            visual_id,
+            &values,
+        );
+        xcb::map_window(&self.conn, id);
+
+        // `cairo::XCBSurface::create` wants cairo's own wrappers around the raw XCB
+        // connection and visual, not the `xcb` crate's types: build them from the
+        // raw pointers, keeping `visual` alive for the duration of the call since the
+        // surface only borrows it.
+        let cairo_conn = unsafe { cairo::XCBConnection::from_raw_none(self.conn.get_raw_conn() as _) };
+        let cairo_visual =
+            unsafe { cairo::XCBVisualType::from_raw_none(&mut visual.base as *mut _ as _) };
+        let surface = cairo::XCBSurface::create(
+            &cairo_conn,
+            &cairo::XCBDrawable(id),
+            &cairo_visual,
+            w as i32,
+            h as i32,
+        )
+        .map_err(|e| PenroseError::Draw(format!("unable to create cairo surface: {:?}", e)))?;
+
+        self.windows.insert(
+            id,
+            WindowState {
+                surface,
+                transparent,
+                colormap,
+            },
+        );
+
+        Ok(id)
+    }
+
+    fn destroy_window(&mut self, id: WinId) -> Result<()> {
+        if let Some(state) = self.windows.remove(&id) {
+            state.surface.finish();
+            if let Some(colormap) = state.colormap {
+                xcb::free_colormap(&self.conn, colormap);
+            }
+        }
+        xcb::destroy_window(&self.conn, id);
+        self.conn.flush();
+
+        Ok(())
+    }
+
+    fn poll_randr_change(&mut self) -> Result<bool> {
+        let first_event = match self.randr_first_event {
+            Some(ev) => ev,
+            None => return Ok(false),
+        };
+
+        let mut changed = false;
+        while let Some(event) = self.conn.poll_for_event() {
+            if event.response_type() & !0x80 == first_event + xcb::randr::SCREEN_CHANGE_NOTIFY {
+                changed = true;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    fn register_font(&mut self, font_name: &str) {
+        self.fonts.push(font_name.to_string());
+    }
+
+    fn context_for(&self, id: WinId) -> Result<XcbDrawContext> {
+        let state = self
+            .windows
+            .get(&id)
+            .ok_or_else(|| PenroseError::Draw(format!("unknown window: {}", id)))?;
+
+        let ctx = cairo::Context::new(&state.surface);
+
+        Ok(XcbDrawContext { ctx, transparent: state.transparent })
+    }
+
+    fn flush(&self) {
+        self.conn.flush();
+    }
+}
+
+/// A [DrawContext] that paints onto a cairo XCB surface. When the backing window was
+/// created with a transparent (ARGB) visual the surface uses a premultiplied-alpha
+/// ARGB32 format, matching what cairo (and X's compositing extension) expect.
+pub struct XcbDrawContext {
+    ctx: cairo::Context,
+    transparent: bool,
+}
+
+impl XcbDrawContext {
+    // cairo stores pixel data premultiplied by alpha: a color's r/g/b channels must be
+    // scaled by its alpha before being handed to the surface, otherwise a compositor
+    // blends the bar using the *unscaled* color and produces a visible halo around
+    // anything painted with partial transparency.
+    fn premultiplied(&self, color: &Color) -> (f64, f64, f64, f64) {
+        if self.transparent {
+            let a = color.alpha();
+            (color.red() * a, color.green() * a, color.blue() * a, a)
+        } else {
+            (color.red(), color.green(), color.blue(), 1.0)
+        }
+    }
+}
+
+impl DrawContext for XcbDrawContext {
+    fn color(&mut self, color: &Color) {
+        let (r, g, b, a) = self.premultiplied(color);
+        self.ctx.set_source_rgba(r, g, b, a);
+    }
+
+    fn rectangle(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.ctx.rectangle(x, y, w, h);
+        self.ctx.fill();
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.ctx.translate(dx, dy);
+    }
+
+    fn save(&mut self) {
+        self.ctx.save();
+    }
+
+    fn restore(&mut self) {
+        self.ctx.restore();
+    }
+
+    fn clip(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.ctx.rectangle(x, y, w, h);
+        self.ctx.clip();
+    }
+}
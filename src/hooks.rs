@@ -0,0 +1,36 @@
+//! Hooks that let external code react to WindowManager events
+use crate::{client::Client, data_types::WinId, WindowManager};
+
+/// A set of callbacks invoked by the WindowManager as it reacts to X11 and user
+/// driven events. All methods are no-ops by default: implementors only need to
+/// override the events that they actually care about.
+pub trait Hook {
+    /// Called whenever a new client is picked up by the WindowManager
+    fn new_client(&mut self, _wm: &mut WindowManager, _c: &mut Client) {}
+
+    /// Called whenever a client is removed from the WindowManager
+    fn remove_client(&mut self, _wm: &mut WindowManager, _id: WinId) {}
+
+    /// Called whenever a client's window title changes. `is_root` is set when the
+    /// update came from the root window rather than from the client itself.
+    fn client_name_updated(
+        &mut self,
+        _wm: &mut WindowManager,
+        _id: WinId,
+        _name: &str,
+        _is_root: bool,
+    ) {
+    }
+
+    /// Called whenever the active layout changes for a workspace on a given screen
+    fn layout_change(&mut self, _wm: &mut WindowManager, _ws_ix: usize, _s_ix: usize) {}
+
+    /// Called whenever the focused workspace changes
+    fn workspace_change(&mut self, _wm: &mut WindowManager, _prev: usize, _new: usize) {}
+
+    /// Called whenever the focused screen changes
+    fn screen_change(&mut self, _wm: &mut WindowManager, _ix: usize) {}
+
+    /// Called whenever focus moves to a different client
+    fn focus_change(&mut self, _wm: &mut WindowManager, _id: WinId) {}
+}
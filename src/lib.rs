@@ -0,0 +1,37 @@
+//! penrose: a tiling window manager in the style of dwm
+#[macro_use]
+extern crate log;
+
+pub mod client;
+pub mod data_types;
+pub mod draw;
+pub mod hooks;
+mod window_manager;
+
+pub use window_manager::WindowManager;
+
+use std::fmt;
+
+/// An error arising from an interaction with the X server or one of penrose's
+/// internal subsystems (drawing, layout, hooks, ...).
+#[derive(Debug)]
+pub enum PenroseError {
+    /// A call into the underlying X11 library failed
+    X11(String),
+    /// Something went wrong while rendering a status bar
+    Draw(String),
+}
+
+impl fmt::Display for PenroseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PenroseError::X11(msg) => write!(f, "X11 error: {}", msg),
+            PenroseError::Draw(msg) => write!(f, "draw error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PenroseError {}
+
+/// Top level result type used throughout penrose
+pub type Result<T> = std::result::Result<T, PenroseError>;
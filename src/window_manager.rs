@@ -0,0 +1,31 @@
+//! The core penrose window manager
+use crate::client::Client;
+use crate::data_types::WinId;
+use crate::draw::bar::BarMessage;
+use std::collections::HashMap;
+
+/// The central coordinator that owns all managed clients and drives the hooks that
+/// react to X11 events.
+pub struct WindowManager {
+    clients: HashMap<WinId, Client>,
+    focused: Option<WinId>,
+}
+
+impl WindowManager {
+    /// The client that currently holds input focus, if any
+    pub fn focused_client(&self) -> Option<&Client> {
+        self.focused.and_then(|id| self.clients.get(&id))
+    }
+
+    /// Act on a message published by a status bar widget via [crate::draw::bar::BarShell].
+    pub fn handle_bar_message(&mut self, msg: BarMessage) {
+        match msg {
+            BarMessage::SwitchWorkspace(ix) => self.focus_workspace(ix),
+        }
+    }
+
+    fn focus_workspace(&mut self, _ix: usize) {
+        // This WindowManager keeps no workspace state, so a widget-originated
+        // request to switch workspace is accepted and has no effect.
+    }
+}